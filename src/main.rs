@@ -1,9 +1,17 @@
+mod replay;
+mod scripting;
+
 use ggegui::{egui, Gui};
 use ggez::glam;
 use ggez::graphics::{
     Camera3d, Canvas3d, DrawParam3d, InstanceArray3d, Mesh3d, Mesh3dBuilder, Shader, ShaderBuilder,
 };
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use replay::Timeline;
+use scripting::{BoidScript, NeighborView};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::{env, path};
 
 use ggez::input::keyboard::KeyCode;
@@ -14,6 +22,14 @@ use ggez::{
     Context, GameResult,
 };
 
+/// Simulation step used by the fixed-timestep accumulator in `MainState::update`,
+/// so flocking behavior stays identical regardless of the rendering frame rate.
+const FIXED_DT: f32 = 1.0 / 60.0;
+/// Caps how much simulated time a single frame can catch up on, so a long
+/// stall (e.g. a stutter or breakpoint) can't spiral into running forever.
+const MAX_ACCUMULATOR: f32 = FIXED_DT * 8.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 struct BoidState {
     max_speed: f32,
     view_distance: f32,
@@ -22,6 +38,10 @@ struct BoidState {
     avoidance: f32,
     centering: f32,
     matching: f32,
+    boid_count: usize,
+    /// Seeds the `StdRng` used for every boid spawn, so a given seed plus the
+    /// parameter timeline it's recorded against always reconstructs the same flock.
+    seed: u64,
 }
 
 impl Default for BoidState {
@@ -34,6 +54,140 @@ impl Default for BoidState {
             avoidance: 0.5,
             centering: 0.075,
             matching: 0.2,
+            boid_count: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// Uniform spatial hash used to turn the O(n^2) neighbor scans in `Boid`'s
+/// steering passes into roughly O(n) lookups. Rebuilt once per tick from the
+/// current boid positions; a query only visits the 27 cells surrounding a
+/// boid's own cell instead of the whole flock.
+struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+}
+
+impl SpatialGrid {
+    fn build(boids: &[Boid], cell_size: f32) -> Self {
+        let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+        for (i, boid) in boids.iter().enumerate() {
+            cells
+                .entry(Self::cell_of(boid.pos, cell_size))
+                .or_default()
+                .push(i);
+        }
+        Self { cell_size, cells }
+    }
+
+    fn cell_of(pos: Vec3, cell_size: f32) -> (i32, i32, i32) {
+        (
+            (pos.x / cell_size).floor() as i32,
+            (pos.y / cell_size).floor() as i32,
+            (pos.z / cell_size).floor() as i32,
+        )
+    }
+
+    /// Visits the index of every boid sharing the 3x3x3 block of cells around `pos`.
+    fn for_each_neighbor(&self, pos: Vec3, mut visit: impl FnMut(usize)) {
+        let (cx, cy, cz) = Self::cell_of(pos, self.cell_size);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(indices) = self.cells.get(&(cx + dx, cy + dy, cz + dz)) {
+                        for &i in indices {
+                            visit(i);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A collider boids steer around. Generalizes the bounding-box wall avoidance
+/// into reusable geometry the flock shares: `Boid::avoid` finds the closest
+/// surface point on every obstacle (plus the bounding box itself, treated as
+/// an `Aabb` the boid stays inside) and repels along the surface normal.
+///
+/// Placements are recorded into `Timeline` alongside `BoidState` edits (see
+/// `replay` module) so a run with obstacles can still be reconstructed from
+/// seed + timeline; this requires glam's `serde` feature for `Vec3`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum Obstacle {
+    Sphere { center: Vec3, radius: f32 },
+    Aabb { center: Vec3, half_extents: Vec3 },
+}
+
+impl Obstacle {
+    fn center(&self) -> Vec3 {
+        match self {
+            Obstacle::Sphere { center, .. } => *center,
+            Obstacle::Aabb { center, .. } => *center,
+        }
+    }
+
+    /// Scales the existing unit cube mesh to roughly match this obstacle's
+    /// footprint for rendering, since there's no dedicated sphere mesh here.
+    fn visual_scale(&self) -> Vec3 {
+        match self {
+            Obstacle::Sphere { radius, .. } => Vec3::splat(*radius),
+            Obstacle::Aabb { half_extents, .. } => *half_extents,
+        }
+    }
+
+    /// Finds the point on the obstacle's surface closest to `point`.
+    fn closest_surface_point(&self, point: Vec3) -> Vec3 {
+        match self {
+            Obstacle::Sphere { center, radius } => {
+                let offset = point - *center;
+                let dir = offset.try_normalize().unwrap_or(Vec3::Y);
+                *center + dir * *radius
+            }
+            Obstacle::Aabb {
+                center,
+                half_extents,
+            } => {
+                let local = point - *center;
+                let clamped = local.clamp(-*half_extents, *half_extents);
+                if clamped == local {
+                    // `point` is inside the box; push out through the nearest face.
+                    let dist_to_face = *half_extents - local.abs();
+                    let face =
+                        if dist_to_face.x <= dist_to_face.y && dist_to_face.x <= dist_to_face.z {
+                            Vec3::new(local.x.signum() * half_extents.x, local.y, local.z)
+                        } else if dist_to_face.y <= dist_to_face.z {
+                            Vec3::new(local.x, local.y.signum() * half_extents.y, local.z)
+                        } else {
+                            Vec3::new(local.x, local.y, local.z.signum() * half_extents.z)
+                        };
+                    *center + face
+                } else {
+                    *center + clamped
+                }
+            }
+        }
+    }
+}
+
+/// Mouse-look flycam controller: WASD+Space/C move the camera at any time,
+/// relative mouse motion steers yaw/pitch while the right mouse button is
+/// held, and the scroll wheel adjusts movement speed.
+struct Flycam {
+    sensitivity: f32,
+    speed: f32,
+    fov_deg: f32,
+    looking: bool,
+}
+
+impl Default for Flycam {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.15,
+            speed: 10.0,
+            fov_deg: 60.0,
+            looking: false,
         }
     }
 }
@@ -46,11 +200,24 @@ struct Boid {
 }
 
 impl Boid {
-    fn update(&mut self, dt: f32, boids: &[Boid], boid_state: &BoidState) {
-        self.center(boids, boid_state);
-        self.avoid(boids, boid_state);
-        self.matching(boids, boid_state);
+    fn update(
+        &mut self,
+        dt: f32,
+        boids: &[Boid],
+        grid: &SpatialGrid,
+        boid_state: &BoidState,
+        obstacles: &[Obstacle],
+    ) {
+        self.center(boids, grid, boid_state);
+        self.avoid(boids, grid, boid_state, obstacles);
+        self.matching(boids, grid, boid_state);
+        self.integrate(dt, boid_state);
+    }
 
+    /// Applies the current velocity to position, bounces off the bounding box
+    /// walls, and clamps speed. Shared by the built-in steering passes and the
+    /// scripted steering path, since both need the same box-collision rules.
+    fn integrate(&mut self, dt: f32, boid_state: &BoidState) {
         // Update position based on velocity
         self.pos += self.vel * dt;
 
@@ -78,25 +245,26 @@ impl Boid {
             self.vel.z *= -1.0;
         }
 
-        self.pos.clamp(Vec3::ZERO, Vec3::splat(boid_state.box_size));
+        self.pos = self.pos.clamp(Vec3::ZERO, Vec3::splat(boid_state.box_size));
 
         // Clamp velocity
-        self.vel.clamp(
+        self.vel = self.vel.clamp(
             Vec3::splat(-boid_state.max_speed),
             Vec3::splat(boid_state.max_speed),
         );
     }
 
-    fn center(&mut self, boids: &[Boid], boid_state: &BoidState) {
+    fn center(&mut self, boids: &[Boid], grid: &SpatialGrid, boid_state: &BoidState) {
         // We get the average center of nearby boids we can see
         let mut center = Vec3::ZERO;
         let mut num_neighbors = 0;
-        for other in boids {
+        grid.for_each_neighbor(self.pos, |i| {
+            let other = boids[i];
             if self.pos.distance(other.pos) < boid_state.view_distance {
                 center += other.pos;
                 num_neighbors += 1;
             }
-        }
+        });
         if num_neighbors > 0 {
             center /= Vec3::splat(num_neighbors as f32);
 
@@ -104,62 +272,69 @@ impl Boid {
         }
     }
 
-    fn avoid(&mut self, boids: &[Boid], boid_state: &BoidState) {
+    fn avoid(
+        &mut self,
+        boids: &[Boid],
+        grid: &SpatialGrid,
+        boid_state: &BoidState,
+        obstacles: &[Obstacle],
+    ) {
         // We add some velocity based off of the boids close to us to avoid said boids
         let mut move_vec = Vec3::default();
-        for other in boids {
+        grid.for_each_neighbor(self.pos, |i| {
+            let other = boids[i];
             let dist = self.pos.distance(other.pos);
             if dist < boid_state.min_distance && dist > 0.0 {
                 move_vec += self.pos - other.pos;
             }
-        }
+        });
         self.vel += move_vec * boid_state.avoidance;
 
-        // Avoid walls
-        let mut move_vec = Vec3::default();
-        // X
-        let dist = (boid_state.box_size - self.pos.x).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.x += self.pos.x - boid_state.box_size;
-        }
-        // Y
-        let dist = (boid_state.box_size - self.pos.y).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.y += self.pos.y - boid_state.box_size;
-        }
-        // Z
-        let dist = (boid_state.box_size - self.pos.z).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.z += self.pos.z - boid_state.box_size;
-        }
-
-        // X
-        let dist = (0.0 - self.pos.x).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.x += self.pos.x;
-        }
-        // Y
-        let dist = (0.0 - self.pos.y).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.y += self.pos.y;
-        }
-        // Z
-        let dist = (0.0 - self.pos.z).abs();
-        if dist < boid_state.min_distance && dist > 0.0 {
-            move_vec.z += self.pos.z;
-        }
-        self.vel += move_vec * 4.0;
+        // Avoid the bounding box walls and any user-placed obstacles through
+        // the same general collider routine: find the closest surface point
+        // and repel along the surface normal, scaled by how deep we are
+        // inside `min_distance`'s influence radius.
+        let bounds = Obstacle::Aabb {
+            center: Vec3::splat(boid_state.box_size / 2.0),
+            half_extents: Vec3::splat(boid_state.box_size / 2.0),
+        };
+        self.avoid_colliders(
+            std::iter::once(bounds).chain(obstacles.iter().copied()),
+            boid_state.min_distance,
+            4.0,
+        );
+    }
+
+    /// Repels away from the surface of each collider in `colliders` that lies
+    /// within `min_distance`, scaled by `strength`.
+    fn avoid_colliders(
+        &mut self,
+        colliders: impl Iterator<Item = Obstacle>,
+        min_distance: f32,
+        strength: f32,
+    ) {
+        let mut move_vec = Vec3::ZERO;
+        for obstacle in colliders {
+            let surface = obstacle.closest_surface_point(self.pos);
+            let offset = self.pos - surface;
+            let dist = offset.length();
+            if dist < min_distance && dist > 0.0 {
+                move_vec += (offset / dist) * (min_distance - dist);
+            }
+        }
+        self.vel += move_vec * strength;
     }
 
-    fn matching(&mut self, boids: &[Boid], boid_state: &BoidState) {
+    fn matching(&mut self, boids: &[Boid], grid: &SpatialGrid, boid_state: &BoidState) {
         let mut avg_vel = Vec3::default();
         let mut num_neighbors = 0;
-        for other in boids {
+        grid.for_each_neighbor(self.pos, |i| {
+            let other = boids[i];
             if self.pos.distance(other.pos) < boid_state.view_distance {
                 avg_vel += other.vel;
                 num_neighbors += 1;
             }
-        }
+        });
         if num_neighbors > 0 {
             avg_vel /= Vec3::splat(num_neighbors as f32);
 
@@ -168,10 +343,10 @@ impl Boid {
     }
 }
 
-impl Default for Boid {
-    fn default() -> Self {
-        let mut rng = rand::thread_rng();
-        let boid_state = BoidState::default();
+impl Boid {
+    /// Spawns a boid with randomized position/velocity/color, drawn from `rng`
+    /// so the whole flock is reproducible from a seed instead of wall-clock entropy.
+    fn spawn(rng: &mut StdRng, boid_state: &BoidState) -> Self {
         Boid {
             pos: Vec3::new(
                 rng.gen_range(0.0..boid_state.box_size),
@@ -197,13 +372,36 @@ struct MainState {
     camera: Camera3d,
     instances: InstanceArray3d,
     boids: Vec<Boid>,
+    /// Boid positions as of the start of the most recent fixed step, used to
+    /// interpolate render positions between simulation steps.
+    prev_positions: Vec<Vec3>,
+    accumulator: f32,
     shader: Shader,
     fancy_shader: Shader,
     boid_state: BoidState,
+    flycam: Flycam,
     gui: Gui,
     cube: Mesh3d,
+    /// Loaded `.rhai` steering script, if any; `step_boids` falls back to the
+    /// built-in passes when this is `None`.
+    script: Option<BoidScript>,
+    script_error: Option<String>,
+    rng: StdRng,
+    /// Number of fixed steps simulated since the last reset; the tick
+    /// `timeline` changes are tagged against.
+    tick: u64,
+    timeline: Timeline,
+    last_recorded_state: BoidState,
+    last_recorded_obstacles: Vec<Obstacle>,
+    obstacles: Vec<Obstacle>,
+    obstacle_instances: InstanceArray3d,
 }
 
+/// Resource path of the steering script the "reload script" button (re)loads.
+const STEERING_SCRIPT_PATH: &str = "/steering.rhai";
+/// Radius given to a sphere obstacle spawned via the "add sphere" button.
+const DEFAULT_OBSTACLE_RADIUS: f32 = 10.0;
+
 impl MainState {
     fn new(ctx: &mut Context) -> GameResult<Self> {
         let mut camera = Camera3d::default();
@@ -213,27 +411,207 @@ impl MainState {
             .pyramid(Vec2::splat(1.0), 2.0, false)
             .build(ctx);
 
-        let mut instances = graphics::InstanceArray3d::new(ctx, None, pyramid);
-        instances.resize(ctx, 100);
+        let obstacle_instances = graphics::InstanceArray3d::new(ctx, None, cube.clone());
 
+        let mut instances = graphics::InstanceArray3d::new(ctx, None, pyramid);
         let boid_state = BoidState::default();
+        instances.resize(ctx, boid_state.boid_count);
+
+        let mut rng = StdRng::seed_from_u64(boid_state.seed);
         let mut boids = Vec::new();
 
-        for _ in 0..100 {
-            boids.push(Boid::default());
+        for _ in 0..boid_state.boid_count {
+            boids.push(Boid::spawn(&mut rng, &boid_state));
         }
+        let prev_positions = boids.iter().map(|b| b.pos).collect();
+
+        let obstacles = Vec::new();
+        let mut timeline = Timeline::new(boid_state.seed);
+        timeline.record(0, &boid_state, &obstacles);
+
+        let last_recorded_state = boid_state.clone();
+        let last_recorded_obstacles = obstacles.clone();
 
         Ok(MainState {
             camera,
             instances,
             boids,
+            prev_positions,
+            accumulator: 0.0,
             shader: ShaderBuilder::from_path("/instance_unordered3d.wgsl").build(ctx)?,
             fancy_shader: ShaderBuilder::from_path("/fancy.wgsl").build(ctx)?,
             boid_state,
+            flycam: Flycam::default(),
             gui: Gui::new(ctx),
             cube,
+            script: None,
+            script_error: None,
+            rng,
+            tick: 0,
+            timeline,
+            last_recorded_state,
+            last_recorded_obstacles,
+            obstacles,
+            obstacle_instances,
         })
     }
+
+    /// (Re)loads the steering script from `STEERING_SCRIPT_PATH`. On failure
+    /// the error is surfaced via `script_error` and the built-in steering
+    /// passes keep running instead of panicking.
+    fn reload_script(&mut self, ctx: &mut Context) {
+        match BoidScript::load(ctx, STEERING_SCRIPT_PATH) {
+            Ok(script) => {
+                self.script = Some(script);
+                self.script_error = None;
+            }
+            Err(err) => {
+                self.script = None;
+                self.script_error = Some(err);
+            }
+        }
+    }
+
+    /// Grows or shrinks the boid flock and the backing instance array to `count`.
+    fn resize_boids(&mut self, ctx: &mut Context, count: usize) {
+        self.instances.resize(ctx, count);
+        if count > self.boids.len() {
+            for _ in self.boids.len()..count {
+                let boid = Boid::spawn(&mut self.rng, &self.boid_state);
+                self.prev_positions.push(boid.pos);
+                self.boids.push(boid);
+            }
+        } else {
+            self.boids.truncate(count);
+            self.prev_positions.truncate(count);
+        }
+    }
+
+    /// Advances every boid by one fixed simulation step. When a steering
+    /// script is loaded it replaces the built-in `center`/`avoid`/`matching`
+    /// passes; either way every boid still avoids the bounding box walls and
+    /// any user-placed obstacles, and integrates through the same
+    /// box-collision rules.
+    fn step_boids(&mut self, dt: f32) {
+        let cell_size = self
+            .boid_state
+            .view_distance
+            .max(self.boid_state.min_distance)
+            .max(f32::EPSILON);
+        let grid = SpatialGrid::build(&self.boids, cell_size);
+
+        if let Some(script) = self.script.as_mut() {
+            let boids = self.boids.clone();
+            for (i, boid) in self.boids.iter_mut().enumerate() {
+                let mut neighbors = Vec::new();
+                grid.for_each_neighbor(boid.pos, |j| {
+                    if j != i {
+                        neighbors.push(NeighborView {
+                            pos: boids[j].pos,
+                            vel: boids[j].vel,
+                        });
+                    }
+                });
+                let accel = script.steer(boid.pos, boid.vel, neighbors);
+                boid.vel += accel * dt;
+
+                // Scripts only control the steering contribution above; every
+                // boid still avoids the bounding box walls and user-placed
+                // obstacles through the same general collider routine the
+                // built-in steering uses, so placed obstacles affect the
+                // whole flock regardless of whether a script is loaded.
+                let bounds = Obstacle::Aabb {
+                    center: Vec3::splat(self.boid_state.box_size / 2.0),
+                    half_extents: Vec3::splat(self.boid_state.box_size / 2.0),
+                };
+                boid.avoid_colliders(
+                    std::iter::once(bounds).chain(self.obstacles.iter().copied()),
+                    self.boid_state.min_distance,
+                    4.0,
+                );
+
+                boid.integrate(dt, &self.boid_state);
+            }
+            self.script_error = script.last_error().map(str::to_owned);
+        } else {
+            for i in 0..self.boids.len() {
+                let mut boid = self.boids[i];
+                boid.update(
+                    dt,
+                    self.boids.as_slice(),
+                    &grid,
+                    &self.boid_state,
+                    &self.obstacles,
+                );
+                self.boids[i] = boid;
+            }
+        }
+    }
+
+    /// Reseeds the RNG from `boid_state.seed` and respawns the flock, starting
+    /// a fresh recorded run so seed + timeline always reconstruct it exactly.
+    fn reseed(&mut self, ctx: &mut Context) {
+        self.rng = StdRng::seed_from_u64(self.boid_state.seed);
+        let count = self.boid_state.boid_count;
+        self.resize_boids(ctx, 0);
+        self.resize_boids(ctx, count);
+        self.tick = 0;
+        self.accumulator = 0.0;
+        self.timeline = Timeline::new(self.boid_state.seed);
+        self.timeline.record(0, &self.boid_state, &self.obstacles);
+        self.last_recorded_state = self.boid_state.clone();
+        self.last_recorded_obstacles = self.obstacles.clone();
+    }
+
+    /// Replays the recorded `timeline` from its seed, reapplying each
+    /// parameter change starting with the step after it was originally made
+    /// (matching when it actually took effect live), to reconstruct the
+    /// exact current flock deterministically.
+    fn replay_from_timeline(&mut self, ctx: &mut Context) {
+        self.rng = StdRng::seed_from_u64(self.timeline.seed);
+        let target_tick = self.tick;
+        // Walk a copy so stepping the simulation can borrow `self` mutably.
+        let timeline = self.timeline.clone();
+        let mut cursor = timeline.replay();
+
+        if let Some(change) = cursor.advance(0) {
+            self.boid_state = change.boid_state.clone();
+            self.obstacles = change.obstacles.clone();
+        }
+        let count = self.boid_state.boid_count;
+        self.resize_boids(ctx, 0);
+        self.resize_boids(ctx, count);
+        self.accumulator = 0.0;
+
+        for tick in 1..=target_tick {
+            // A change is recorded with the tick count as of just after that
+            // many fixed steps had already run with the old state, so it
+            // only takes effect starting with the following step: the entry
+            // tagged `tick - 1` applies here, not the entry tagged `tick`.
+            if let Some(change) = cursor.advance(tick - 1) {
+                self.boid_state = change.boid_state.clone();
+                self.obstacles = change.obstacles.clone();
+                if self.boids.len() != self.boid_state.boid_count {
+                    let count = self.boid_state.boid_count;
+                    self.resize_boids(ctx, count);
+                }
+            }
+            self.step_boids(FIXED_DT);
+        }
+        // Changes tagged `target_tick` were made after the last step ran, so
+        // no step has used them yet, but they are already part of the live state.
+        if let Some(change) = cursor.advance(target_tick) {
+            self.boid_state = change.boid_state.clone();
+            self.obstacles = change.obstacles.clone();
+            if self.boids.len() != self.boid_state.boid_count {
+                let count = self.boid_state.boid_count;
+                self.resize_boids(ctx, count);
+            }
+        }
+        self.tick = target_tick;
+        self.last_recorded_state = self.boid_state.clone();
+        self.last_recorded_obstacles = self.obstacles.clone();
+    }
 }
 
 impl event::EventHandler for MainState {
@@ -244,19 +622,23 @@ impl event::EventHandler for MainState {
         Ok(())
     }
     fn update(&mut self, ctx: &mut Context) -> GameResult {
-        // Update boids:
-        for i in 0..self.boids.len() {
-            let mut boid = self.boids[i];
-            boid.update(
-                ctx.time.delta().as_secs_f32(),
-                self.boids.as_slice(),
-                &self.boid_state,
-            );
-            self.boids[i] = boid;
+        // Update boids on a fixed timestep so behavior is frame-rate independent;
+        // the accumulator is clamped to avoid a spiral of death after a stall.
+        self.accumulator = (self.accumulator + ctx.time.delta().as_secs_f32()).min(MAX_ACCUMULATOR);
+        while self.accumulator >= FIXED_DT {
+            self.prev_positions = self.boids.iter().map(|b| b.pos).collect();
+            self.step_boids(FIXED_DT);
+            self.accumulator -= FIXED_DT;
+            self.tick += 1;
         }
 
         // GUI
         let gui_ctx = self.gui.ctx();
+        let mut reset = false;
+        let mut replay = false;
+        let mut reload_script = false;
+        let mut add_sphere = false;
+        let mut remove_obstacle = None;
 
         egui::Window::new("UI").show(&gui_ctx, |ui| {
             ui.horizontal(|ui| {
@@ -299,14 +681,74 @@ impl event::EventHandler for MainState {
                     0.0..=100.0,
                 ));
             });
+            ui.horizontal(|ui| {
+                ui.label("Boid Count: ");
+                ui.add(egui::Slider::new(
+                    &mut self.boid_state.boid_count,
+                    1..=20000,
+                ));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Camera Sensitivity: ");
+                ui.add(egui::Slider::new(&mut self.flycam.sensitivity, 0.01..=1.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Camera Speed: ");
+                ui.add(egui::Slider::new(&mut self.flycam.speed, 1.0..=200.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Field of View: ");
+                ui.add(egui::Slider::new(&mut self.flycam.fov_deg, 30.0..=120.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Seed: ");
+                ui.add(egui::DragValue::new(&mut self.boid_state.seed));
+            });
+            ui.label(format!(
+                "Tick {} | {} recorded changes",
+                self.tick,
+                self.timeline.changes.len()
+            ));
+
+            ui.separator();
+            ui.label("Obstacles:");
+            for (i, obstacle) in self.obstacles.iter_mut().enumerate() {
+                if let Obstacle::Sphere { center, radius } = obstacle {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("Sphere {i}: "));
+                        ui.add(egui::DragValue::new(&mut center.x).prefix("x: "));
+                        ui.add(egui::DragValue::new(&mut center.y).prefix("y: "));
+                        ui.add(egui::DragValue::new(&mut center.z).prefix("z: "));
+                        ui.add(
+                            egui::DragValue::new(radius)
+                                .prefix("r: ")
+                                .clamp_range(0.1..=f32::MAX),
+                        );
+                        if ui.button("remove").clicked() {
+                            remove_obstacle = Some(i);
+                        }
+                    });
+                }
+            }
+            if ui.button("add sphere").clicked() {
+                add_sphere = true;
+            }
 
             if ui.button("reset").clicked() {
-                let mut boids = Vec::new();
-
-                for _ in 0..100 {
-                    boids.push(Boid::default());
-                }
-                self.boids = boids;
+                reset = true;
+            }
+            if ui.button("replay from seed").clicked() {
+                replay = true;
+            }
+            if ui.button("reload script").clicked() {
+                reload_script = true;
+            }
+            if self.script.is_some() && ui.button("unload script").clicked() {
+                self.script = None;
+                self.script_error = None;
+            }
+            if let Some(err) = &self.script_error {
+                ui.colored_label(egui::Color32::RED, format!("script error: {err}"));
             }
             if ui.button("quit").clicked() {
                 ctx.request_quit();
@@ -314,45 +756,123 @@ impl event::EventHandler for MainState {
         });
         self.gui.update(ctx);
 
+        if add_sphere {
+            self.obstacles.push(Obstacle::Sphere {
+                center: Vec3::splat(self.boid_state.box_size / 2.0),
+                radius: DEFAULT_OBSTACLE_RADIUS,
+            });
+        }
+        if let Some(i) = remove_obstacle {
+            self.obstacles.remove(i);
+        }
+
+        if reload_script {
+            self.reload_script(ctx);
+        }
+
+        if replay {
+            self.replay_from_timeline(ctx);
+        } else if reset {
+            self.reseed(ctx);
+        } else if self.boids.len() != self.boid_state.boid_count {
+            let count = self.boid_state.boid_count;
+            self.resize_boids(ctx, count);
+        }
+
+        if !replay
+            && !reset
+            && (self.boid_state != self.last_recorded_state
+                || self.obstacles != self.last_recorded_obstacles)
+        {
+            self.timeline
+                .record(self.tick, &self.boid_state, &self.obstacles);
+            self.last_recorded_state = self.boid_state.clone();
+            self.last_recorded_obstacles = self.obstacles.clone();
+        }
+
         // Input
+        self.camera.projection.fovy = self.flycam.fov_deg.to_radians();
+
         let k_ctx = &ctx.keyboard.clone();
         let (yaw_sin, yaw_cos) = self.camera.transform.yaw.sin_cos();
         let forward = Vec3::new(yaw_cos, 0.0, yaw_sin).normalize();
         let right = Vec3::new(-yaw_sin, 0.0, yaw_cos).normalize();
+        let move_amount = self.flycam.speed * ctx.time.delta().as_secs_f32();
 
         if k_ctx.is_key_pressed(KeyCode::Space) {
-            self.camera.transform.position.y += 1.0;
+            self.camera.transform.position.y += move_amount;
         }
         if k_ctx.is_key_pressed(KeyCode::C) {
-            self.camera.transform.position.y -= 1.0;
+            self.camera.transform.position.y -= move_amount;
         }
         if k_ctx.is_key_pressed(KeyCode::W) {
-            self.camera.transform = self.camera.transform.translate(forward);
+            self.camera.transform = self.camera.transform.translate(forward * move_amount);
         }
         if k_ctx.is_key_pressed(KeyCode::S) {
-            self.camera.transform = self.camera.transform.translate(-forward);
+            self.camera.transform = self.camera.transform.translate(-forward * move_amount);
         }
         if k_ctx.is_key_pressed(KeyCode::D) {
-            self.camera.transform = self.camera.transform.translate(right);
+            self.camera.transform = self.camera.transform.translate(right * move_amount);
         }
         if k_ctx.is_key_pressed(KeyCode::A) {
-            self.camera.transform = self.camera.transform.translate(-right);
-        }
-        if k_ctx.is_key_pressed(KeyCode::Right) {
-            self.camera.transform.yaw += 1.0_f32.to_radians();
+            self.camera.transform = self.camera.transform.translate(-right * move_amount);
         }
-        if k_ctx.is_key_pressed(KeyCode::Left) {
-            self.camera.transform.yaw -= 1.0_f32.to_radians();
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        ctx: &mut Context,
+        button: event::MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> GameResult {
+        if button == event::MouseButton::Right {
+            self.flycam.looking = true;
+            ctx.mouse.set_cursor_grabbed(true)?;
+            ctx.mouse.set_cursor_hidden(true);
         }
-        if k_ctx.is_key_pressed(KeyCode::Up) {
-            self.camera.transform.pitch += 1.0_f32.to_radians();
+        Ok(())
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        ctx: &mut Context,
+        button: event::MouseButton,
+        _x: f32,
+        _y: f32,
+    ) -> GameResult {
+        if button == event::MouseButton::Right {
+            self.flycam.looking = false;
+            ctx.mouse.set_cursor_grabbed(false)?;
+            ctx.mouse.set_cursor_hidden(false);
         }
-        if k_ctx.is_key_pressed(KeyCode::Down) {
-            self.camera.transform.pitch -= 1.0_f32.to_radians();
+        Ok(())
+    }
+
+    fn mouse_motion_event(
+        &mut self,
+        _ctx: &mut Context,
+        _x: f32,
+        _y: f32,
+        dx: f32,
+        dy: f32,
+    ) -> GameResult {
+        if self.flycam.looking {
+            let pitch_limit = 89.0_f32.to_radians();
+            self.camera.transform.yaw += dx * self.flycam.sensitivity.to_radians();
+            self.camera.transform.pitch = (self.camera.transform.pitch
+                - dy * self.flycam.sensitivity.to_radians())
+            .clamp(-pitch_limit, pitch_limit);
         }
         Ok(())
     }
 
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) -> GameResult {
+        self.flycam.speed = (self.flycam.speed + y).max(0.1);
+        Ok(())
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
         let mut canvas3d = Canvas3d::from_frame(ctx, Color::new(0.25, 0.25, 0.25, 1.0));
         canvas3d.set_projection(self.camera.to_matrix());
@@ -366,9 +886,14 @@ impl event::EventHandler for MainState {
         );
         canvas3d.set_shader(&self.shader);
 
+        // Interpolate between the previous and current simulation step so
+        // rendering stays smooth independent of the fixed simulation rate.
+        let alpha = self.accumulator / FIXED_DT;
+
         // Set rotation, position, and color for boids
-        self.instances.set((0..100).map(|i| {
-            let direction = self.boids[i].pos + (self.boids[i].vel * 10.0);
+        self.instances.set((0..self.boids.len()).map(|i| {
+            let pos = self.prev_positions[i].lerp(self.boids[i].pos, alpha);
+            let direction = pos + (self.boids[i].vel * 10.0);
             let up = Vec3::Y;
             let back = -direction.try_normalize().unwrap_or(Vec3::NEG_Z);
             let right = up
@@ -377,7 +902,7 @@ impl event::EventHandler for MainState {
                 .unwrap_or_else(|| up.any_orthonormal_vector());
             let up = back.cross(right);
             graphics::DrawParam3d::default()
-                .position(self.boids[i].pos / Vec3::splat(2.0))
+                .position(pos / Vec3::splat(2.0))
                 .color(self.boids[i].col)
                 .rotation(
                     Quat::from_mat3(&Mat3::from_cols(right, up, back))
@@ -392,6 +917,19 @@ impl event::EventHandler for MainState {
 
         canvas3d.draw(&self.instances, param);
 
+        // Render obstacles through the same mesh/instance-array path as boids.
+        self.obstacle_instances.resize(ctx, self.obstacles.len());
+        self.obstacle_instances.set(self.obstacles.iter().map(|o| {
+            graphics::DrawParam3d::default()
+                .position(o.center())
+                .scale(o.visual_scale())
+                .color(Color::new(1.0, 0.3, 0.3, 0.6))
+        }));
+        canvas3d.draw(
+            &self.obstacle_instances,
+            graphics::DrawParam3d::default().color(Color::new(1.0, 1.0, 1.0, 1.0)),
+        );
+
         canvas3d.finish(ctx)?;
         let mut canvas = graphics::Canvas::from_frame(ctx, None);
 
@@ -0,0 +1,77 @@
+//! Record/replay of parameter changes, so a seed plus a timeline of
+//! `BoidState` and `Obstacle` edits is enough to reconstruct an exact run.
+//! This is the foundation for reproducible "this configuration explodes"
+//! bug reports and for later deterministic networking.
+
+use crate::{BoidState, Obstacle};
+use serde::{Deserialize, Serialize};
+
+/// One edit to `BoidState` and/or the placed `Obstacle`s made through the
+/// egui panel, tagged with the fixed-timestep tick count as of when it was
+/// made. It takes effect starting with the *next* step (tick 0 is the
+/// initial baseline and takes effect immediately, before any steps have
+/// run). Both fields are always snapshotted together so replay never has
+/// to reason about which one actually changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParamChange {
+    pub tick: u64,
+    pub boid_state: BoidState,
+    pub obstacles: Vec<Obstacle>,
+}
+
+/// Seed plus the ordered list of parameter changes needed to reproduce a run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Timeline {
+    pub seed: u64,
+    pub changes: Vec<ParamChange>,
+}
+
+impl Timeline {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, tick: u64, boid_state: &BoidState, obstacles: &[Obstacle]) {
+        self.changes.push(ParamChange {
+            tick,
+            boid_state: boid_state.clone(),
+            obstacles: obstacles.to_vec(),
+        });
+    }
+
+    /// Returns a cursor that feeds `changes` back in tick order while replaying.
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            timeline: self,
+            next: 0,
+        }
+    }
+}
+
+/// Walks a `Timeline` forward in lock-step with the fixed-timestep simulation.
+pub struct Replay<'a> {
+    timeline: &'a Timeline,
+    next: usize,
+}
+
+impl<'a> Replay<'a> {
+    /// Returns the `ParamChange` to apply at `tick`, if any changes landed
+    /// there. Multiple changes can share a tick (e.g. the tick-0 baseline
+    /// recorded at construction plus a user edit made before the first fixed
+    /// step also lands on tick 0), so every matching change is drained in
+    /// order and the most recent one is returned.
+    pub fn advance(&mut self, tick: u64) -> Option<&'a ParamChange> {
+        let mut latest = None;
+        while let Some(change) = self.timeline.changes.get(self.next) {
+            if change.tick != tick {
+                break;
+            }
+            latest = Some(change);
+            self.next += 1;
+        }
+        latest
+    }
+}
@@ -0,0 +1,96 @@
+//! Embeds Rhai so steering behavior can be edited without recompiling.
+//! Requires the `rhai` dependency's `f32_float` feature (so `rhai::FLOAT`
+//! matches `f32` and scripts can feed numbers straight into `Vec3` math)
+//! and `sync` feature (so `Engine`/`AST` stay `Send + Sync` alongside the
+//! rest of the game state).
+
+use ggez::glam::Vec3;
+use ggez::Context;
+use rhai::{Dynamic, Engine, Scope, AST};
+use std::io::Read;
+
+/// A read-only view of one neighbor boid, handed to a `steer` script. The
+/// script receives every other boid in the 3x3x3 block of grid cells around
+/// it (the candidates the built-in `center`/`avoid`/`matching` passes
+/// distance-test); the list is not filtered by distance.
+#[derive(Debug, Clone, Copy)]
+pub struct NeighborView {
+    pub pos: Vec3,
+    pub vel: Vec3,
+}
+
+/// An optional `.rhai` steering script loaded from the resources dir. When
+/// present, `MainState::step_boids` calls it per boid instead of the
+/// built-in steering passes; compile and runtime errors are captured here
+/// rather than panicking so they can be surfaced in the egui panel.
+pub struct BoidScript {
+    engine: Engine,
+    ast: AST,
+    last_error: Option<String>,
+}
+
+impl BoidScript {
+    /// Compiles the script at the given resource path (e.g. `/steering.rhai`),
+    /// registering `Vec3` math and `NeighborView` so it can be used from script.
+    pub fn load(ctx: &mut Context, path: &str) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        register_api(&mut engine);
+
+        let mut file = ctx.fs.open(path).map_err(|e| e.to_string())?;
+        let mut source = String::new();
+        file.read_to_string(&mut source)
+            .map_err(|e| e.to_string())?;
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+
+        Ok(Self {
+            engine,
+            ast,
+            last_error: None,
+        })
+    }
+
+    /// Calls the script's `steer(pos, vel, neighbors)` function and returns the
+    /// acceleration it computes. A runtime error is stashed in `last_error` and
+    /// zero acceleration is returned instead of propagating the error up into
+    /// the simulation step.
+    pub fn steer(&mut self, pos: Vec3, vel: Vec3, neighbors: Vec<NeighborView>) -> Vec3 {
+        let neighbors: Vec<Dynamic> = neighbors.into_iter().map(Dynamic::from).collect();
+        let mut scope = Scope::new();
+        match self
+            .engine
+            .call_fn::<Vec3>(&mut scope, &self.ast, "steer", (pos, vel, neighbors))
+        {
+            Ok(accel) => {
+                self.last_error = None;
+                accel
+            }
+            Err(err) => {
+                self.last_error = Some(err.to_string());
+                Vec3::ZERO
+            }
+        }
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+
+fn register_api(engine: &mut Engine) {
+    engine
+        .register_type_with_name::<Vec3>("Vec3")
+        .register_fn("vec3", |x: f32, y: f32, z: f32| Vec3::new(x, y, z))
+        .register_get("x", |v: &mut Vec3| v.x)
+        .register_get("y", |v: &mut Vec3| v.y)
+        .register_get("z", |v: &mut Vec3| v.z)
+        .register_fn("+", |a: Vec3, b: Vec3| a + b)
+        .register_fn("-", |a: Vec3, b: Vec3| a - b)
+        .register_fn("*", |a: Vec3, b: f32| a * b)
+        .register_fn("length", |v: Vec3| v.length())
+        .register_fn("normalize", |v: Vec3| v.normalize_or_zero());
+
+    engine
+        .register_type_with_name::<NeighborView>("Neighbor")
+        .register_get("pos", |n: &mut NeighborView| n.pos)
+        .register_get("vel", |n: &mut NeighborView| n.vel);
+}